@@ -1,13 +1,15 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
+use minisign_verify::{PublicKey, Signature};
 use reqwest::blocking::Client;
 use semver::Version;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
 use std::ffi::OsStr;
 use std::env;
 use std::fs;
 use std::fs::OpenOptions;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
@@ -21,14 +23,23 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 const APP_EXE: &str = "LetMeSleep.exe";
 const UPDATER_EXE: &str = "LetMeSleep-Updater.exe";
+const BACKUP_DIR: &str = "LetMeSleep-backup";
+const STAGED_DIR: &str = "LetMeSleep-staged";
+const PENDING_VERSION_FILE: &str = "LetMeSleep-pending-update";
+const APPLY_POLL_TIMEOUT: Duration = Duration::from_secs(30);
 const REPO: &str = "cytsai1008/let-me-sleep";
 #[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x0800_0000;
 
+// Public half of the release signing key; the private half never leaves CI.
+const PUBKEY: &str = "RWQsLgZhi6IpcxEWXbCdqwA6BR0HdmKG4TTVrnkoVyPVOzkiDZ7ZWVYl";
+
 #[derive(Deserialize)]
 struct Release {
     tag_name: String,
     assets: Vec<Asset>,
+    #[serde(default)]
+    prerelease: bool,
 }
 
 #[derive(Deserialize)]
@@ -36,6 +47,37 @@ struct Asset {
     name: String,
     browser_download_url: String,
     size: u64,
+    digest: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Stable,
+    Beta,
+}
+
+impl Channel {
+    fn parse(s: &str) -> Option<Channel> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "stable" => Some(Channel::Stable),
+            "beta" => Some(Channel::Beta),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+        }
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        match self {
+            Channel::Stable => version.pre.is_empty(),
+            Channel::Beta => version.pre.as_str().starts_with("beta"),
+        }
+    }
 }
 
 struct Logger {
@@ -86,14 +128,32 @@ fn get_current_version(app_dir: &Path) -> Option<Version> {
     parse_version(content.trim())
 }
 
-fn check_for_update(
+fn fetch_latest_release(client: &Client, repo: &str, logger: &mut Logger) -> Option<Release> {
+    let url = format!("https://api.github.com/repos/{repo}/releases/latest");
+    logger.log(format!("Checking latest release: {url}"));
+
+    let resp = client
+        .get(&url)
+        .header("User-Agent", "LetMeSleep-Updater")
+        .send()
+        .ok()?;
+
+    if !resp.status().is_success() {
+        logger.log(format!("Update check HTTP status: {}", resp.status()));
+        return None;
+    }
+
+    resp.json().ok()
+}
+
+fn fetch_best_release_for_channel(
     client: &Client,
     repo: &str,
-    current: &Version,
+    channel: Channel,
     logger: &mut Logger,
-) -> Option<(String, Asset)> {
-    let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
-    logger.log(format!("Checking latest release: {url}"));
+) -> Option<Release> {
+    let url = format!("https://api.github.com/repos/{repo}/releases");
+    logger.log(format!("Checking releases for {} channel: {url}", channel.as_str()));
 
     let resp = client
         .get(&url)
@@ -106,7 +166,29 @@ fn check_for_update(
         return None;
     }
 
-    let release: Release = resp.json().ok()?;
+    let releases: Vec<Release> = resp.json().ok()?;
+    releases
+        .into_iter()
+        .filter(|r| r.prerelease)
+        .filter(|r| {
+            parse_version(&r.tag_name)
+                .map(|v| channel.matches(&v))
+                .unwrap_or(false)
+        })
+        .max_by_key(|r| parse_version(&r.tag_name))
+}
+
+fn check_for_update(
+    client: &Client,
+    repo: &str,
+    current: &Version,
+    channel: Channel,
+    logger: &mut Logger,
+) -> Option<(String, Asset, Asset)> {
+    let release = match channel {
+        Channel::Stable => fetch_latest_release(client, repo, logger)?,
+        Channel::Beta => fetch_best_release_for_channel(client, repo, channel, logger)?,
+    };
     let latest = parse_version(&release.tag_name)?;
 
     if latest <= *current {
@@ -115,6 +197,8 @@ fn check_for_update(
     }
 
     let asset = release.assets.iter().find(|a| a.name.ends_with(".zip"))?;
+    let sig_name = format!("{}.sig", asset.name);
+    let sig_asset = release.assets.iter().find(|a| a.name == sig_name)?;
 
     Some((
         release.tag_name.clone(),
@@ -122,31 +206,153 @@ fn check_for_update(
             name: asset.name.clone(),
             browser_download_url: asset.browser_download_url.clone(),
             size: asset.size,
+            digest: asset.digest.clone(),
+        },
+        Asset {
+            name: sig_asset.name.clone(),
+            browser_download_url: sig_asset.browser_download_url.clone(),
+            size: sig_asset.size,
+            digest: sig_asset.digest.clone(),
         },
     ))
 }
 
+fn download_text(client: &Client, url: &str, logger: &mut Logger) -> Result<String, String> {
+    logger.log(format!("Downloading {url}"));
+    client
+        .get(url)
+        .header("User-Agent", "LetMeSleep-Updater")
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| format!("Download failed: {e}"))
+}
+
+fn verify_signature(zip_bytes: &[u8], sig_str: &str, logger: &mut Logger) -> Result<(), String> {
+    let pubkey = PublicKey::from_base64(PUBKEY).map_err(|e| format!("Invalid public key: {e}"))?;
+    let sig = Signature::decode_from_string(sig_str).map_err(|e| format!("Invalid signature: {e}"))?;
+
+    pubkey
+        .verify(zip_bytes, &sig, false)
+        .map_err(|e| format!("Signature verification failed: {e}"))?;
+
+    logger.log("Signature verified");
+    Ok(())
+}
+
+fn download_meta_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".meta");
+    PathBuf::from(name)
+}
+
 fn download(
     client: &Client,
     asset: &Asset,
     dest: &Path,
     logger: &mut Logger,
 ) -> Result<(), String> {
+    let meta_path = download_meta_path(dest);
+    let expected_meta = format!("{}:{}", asset.name, asset.size);
+    let meta_matches = fs::read_to_string(&meta_path)
+        .map(|s| s.trim() == expected_meta)
+        .unwrap_or(false);
+
+    let existing_len = fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+    let resume = meta_matches && existing_len > 0 && existing_len < asset.size;
+    if existing_len > 0 && !meta_matches {
+        logger.log("Leftover partial download belongs to a different asset; restarting");
+    }
+
     logger.log(format!(
-        "Downloading {} ({:.1} MB)",
+        "Downloading {} ({:.1} MB){}",
         asset.name,
-        asset.size as f64 / 1_048_576.0
+        asset.size as f64 / 1_048_576.0,
+        if resume { ", resuming" } else { "" }
     ));
 
-    let bytes = client
+    let mut request = client
         .get(&asset.browser_download_url)
-        .header("User-Agent", "LetMeSleep-Updater")
+        .header("User-Agent", "LetMeSleep-Updater");
+    if resume {
+        request = request.header("Range", format!("bytes={existing_len}-"));
+    }
+
+    let mut resp = request
         .send()
         .and_then(|r| r.error_for_status())
-        .and_then(|r| r.bytes())
         .map_err(|e| format!("Download failed: {e}"))?;
 
-    fs::write(dest, &bytes).map_err(|e| format!("Write failed: {e}"))?;
+    let mut hasher = Sha256::new();
+    let (mut file, mut downloaded) = if resume && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        logger.log(format!("Resuming from {existing_len} bytes"));
+        let mut existing = fs::File::open(dest).map_err(|e| format!("Read existing: {e}"))?;
+        let mut hash_buf = [0u8; 64 * 1024];
+        loop {
+            let n = existing
+                .read(&mut hash_buf)
+                .map_err(|e| format!("Read existing: {e}"))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&hash_buf[..n]);
+        }
+        let file = OpenOptions::new()
+            .append(true)
+            .open(dest)
+            .map_err(|e| format!("Open for resume: {e}"))?;
+        (file, existing_len)
+    } else {
+        if resume {
+            logger.log("Server did not honor range request; restarting download");
+        }
+        let file = fs::File::create(dest).map_err(|e| format!("Create {}: {e}", dest.display()))?;
+        fs::write(&meta_path, &expected_meta).map_err(|e| format!("Write download metadata: {e}"))?;
+        (file, 0)
+    };
+
+    let mut buf = [0u8; 64 * 1024];
+    let mut last_reported_pct = 0u64;
+    loop {
+        let n = resp
+            .read(&mut buf)
+            .map_err(|e| format!("Download failed: {e}"))?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buf[..n]).map_err(|e| format!("Write failed: {e}"))?;
+        hasher.update(&buf[..n]);
+        downloaded += n as u64;
+
+        let pct = if asset.size > 0 { downloaded * 100 / asset.size } else { 0 };
+        if pct >= last_reported_pct + 5 {
+            logger.log(format!(
+                "Downloaded {:.1}/{:.1} MB ({pct}%)",
+                downloaded as f64 / 1_048_576.0,
+                asset.size as f64 / 1_048_576.0
+            ));
+            last_reported_pct = pct;
+        }
+    }
+
+    if downloaded != asset.size {
+        return Err(format!(
+            "Size mismatch: expected {} bytes, got {downloaded}",
+            asset.size
+        ));
+    }
+
+    if let Some(digest) = &asset.digest {
+        let expected = digest.strip_prefix("sha256:").unwrap_or(digest);
+        let actual = format!("{:x}", hasher.finalize());
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "Digest mismatch: expected {expected}, got {actual}"
+            ));
+        }
+        logger.log("Digest verified");
+    }
+
     logger.log("Download complete");
     Ok(())
 }
@@ -159,6 +365,229 @@ fn kill_app(process_name: &str, logger: &mut Logger) {
     thread::sleep(Duration::from_secs(2));
 }
 
+const MANIFEST_FILE: &str = "MANIFEST";
+
+fn top_level_entries(app_dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Ok(entries) = fs::read_dir(app_dir) {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                if name == BACKUP_DIR || name == STAGED_DIR {
+                    continue;
+                }
+                names.push(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+fn dist_dirs(app_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(entries) = fs::read_dir(app_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_dist = path.is_dir()
+                && path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .map(|name| name.ends_with(".dist"))
+                    .unwrap_or(false);
+            if is_dist {
+                dirs.push(path);
+            }
+        }
+    }
+    dirs
+}
+
+fn create_backup(app_dir: &Path, logger: &mut Logger) -> Result<PathBuf, String> {
+    let backup_dir = app_dir.join(BACKUP_DIR);
+    if backup_dir.exists() {
+        fs::remove_dir_all(&backup_dir).map_err(|e| format!("Clear stale backup: {e}"))?;
+    }
+    fs::create_dir_all(&backup_dir).map_err(|e| format!("Create backup dir: {e}"))?;
+
+    // Snapshot what's here before extraction touches anything, so a rollback can
+    // tell apart the original install from whatever a failed extraction dropped in.
+    let preexisting = top_level_entries(app_dir).join("\n");
+    fs::write(backup_dir.join(MANIFEST_FILE), preexisting)
+        .map_err(|e| format!("Write backup manifest: {e}"))?;
+
+    let app_exe = app_dir.join(APP_EXE);
+    if app_exe.exists() {
+        fs::rename(&app_exe, backup_dir.join(APP_EXE))
+            .map_err(|e| format!("Backup {APP_EXE}: {e}"))?;
+    }
+
+    let version_file = app_dir.join("VERSION");
+    if version_file.exists() {
+        fs::rename(&version_file, backup_dir.join("VERSION"))
+            .map_err(|e| format!("Backup VERSION: {e}"))?;
+    }
+
+    for dist_dir in dist_dirs(app_dir) {
+        let Some(name) = dist_dir.file_name() else {
+            continue;
+        };
+        fs::rename(&dist_dir, backup_dir.join(name))
+            .map_err(|e| format!("Backup {}: {e}", dist_dir.display()))?;
+    }
+
+    logger.log(format!("Backed up current install to {BACKUP_DIR}"));
+    Ok(backup_dir)
+}
+
+fn restore_backup(app_dir: &Path, backup_dir: &Path, logger: &mut Logger) {
+    let Ok(entries) = fs::read_dir(backup_dir) else {
+        logger.log(format!("Backup dir missing, cannot restore: {}", backup_dir.display()));
+        return;
+    };
+
+    let preexisting: Vec<String> = fs::read_to_string(backup_dir.join(MANIFEST_FILE))
+        .map(|manifest| manifest.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    for entry in entries.flatten() {
+        let src = entry.path();
+        let Some(name) = src.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+        if name == MANIFEST_FILE {
+            continue;
+        }
+        let dst = app_dir.join(name);
+
+        if dst.is_dir() {
+            let _ = fs::remove_dir_all(&dst);
+        } else {
+            let _ = fs::remove_file(&dst);
+        }
+
+        if let Err(e) = fs::rename(&src, &dst) {
+            logger.log(format!("Failed to restore {}: {e}", pretty_path(&dst, app_dir)));
+        }
+    }
+
+    // Anything a failed extraction introduced that wasn't part of the original
+    // install is debris now, not a restored file — clear it so a stray partial
+    // directory (e.g. a new-version `.dist`) can't be picked up over the restore.
+    if let Ok(entries) = fs::read_dir(app_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_known = path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .map(|name| {
+                    name == BACKUP_DIR || name == STAGED_DIR || preexisting.iter().any(|p| p == name)
+                })
+                .unwrap_or(true);
+            if is_known {
+                continue;
+            }
+
+            logger.log(format!(
+                "Removing extraction debris: {}",
+                pretty_path(&path, app_dir)
+            ));
+            if path.is_dir() {
+                let _ = fs::remove_dir_all(&path);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    let _ = fs::remove_dir_all(backup_dir);
+}
+
+fn move_staged_into(app_dir: &Path, staged_dir: &Path, logger: &mut Logger) -> Result<(), String> {
+    let entries = fs::read_dir(staged_dir).map_err(|e| format!("Read staged dir: {e}"))?;
+    for entry in entries.flatten() {
+        let src = entry.path();
+        let Some(name) = src.file_name() else {
+            continue;
+        };
+        let dst = app_dir.join(name);
+
+        if dst.is_dir() {
+            fs::remove_dir_all(&dst).map_err(|e| format!("Remove {}: {e}", dst.display()))?;
+        } else if dst.exists() {
+            fs::remove_file(&dst).map_err(|e| format!("Remove {}: {e}", dst.display()))?;
+        }
+
+        fs::rename(&src, &dst).map_err(|e| format!("Move {}: {e}", src.display()))?;
+    }
+
+    let _ = fs::remove_dir_all(staged_dir);
+    logger.log("Staged payload applied");
+    Ok(())
+}
+
+fn discard_pending_update(app_dir: &Path, logger: &mut Logger, reason: &str) {
+    logger.log(format!("Discarding pending staged update: {reason}"));
+    let _ = fs::remove_dir_all(app_dir.join(STAGED_DIR));
+    let _ = fs::remove_file(app_dir.join(PENDING_VERSION_FILE));
+}
+
+/// Applies a previously staged update. Returns `Ok(true)` if it was applied,
+/// `Ok(false)` if the pending marker was stale and discarded without touching
+/// the live install.
+fn apply_pending_update(app_dir: &Path, logger: &mut Logger) -> Result<bool, String> {
+    let marker = app_dir.join(PENDING_VERSION_FILE);
+    let version = fs::read_to_string(&marker).map_err(|e| format!("Read pending marker: {e}"))?;
+    let version = version.trim().to_string();
+    let staged_dir = app_dir.join(STAGED_DIR);
+
+    let pending = parse_version(&version);
+    let current = get_current_version(app_dir);
+    let is_newer = match (&pending, &current) {
+        (Some(pending), Some(current)) => pending > current,
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    if !is_newer {
+        discard_pending_update(
+            app_dir,
+            logger,
+            &format!("v{version} is not newer than the installed version"),
+        );
+        return Ok(false);
+    }
+
+    logger.log(format!("Applying staged update: v{version}"));
+
+    let backup_dir = match create_backup(app_dir, logger) {
+        Ok(backup_dir) => backup_dir,
+        Err(e) => {
+            logger.log(format!("ERROR: {e}"));
+            discard_pending_update(app_dir, logger, "failed to apply and cannot be retried safely");
+            return Err(e);
+        }
+    };
+
+    let result = move_staged_into(app_dir, &staged_dir, logger)
+        .and_then(|()| fs::write(app_dir.join("VERSION"), &version).map_err(|e| format!("Write version: {e}")));
+
+    match result {
+        Ok(()) => {
+            let _ = fs::remove_dir_all(&backup_dir);
+            let _ = fs::remove_file(&marker);
+            schedule_updater_replacement(app_dir, logger);
+            logger.log(format!("Staged update applied: v{version}"));
+            Ok(true)
+        }
+        Err(e) => {
+            logger.log(format!("ERROR: {e}"));
+            restore_backup(app_dir, &backup_dir, logger);
+            logger.log("Rolled back staged update");
+            discard_pending_update(app_dir, logger, "failed to apply and cannot be retried safely");
+            Err(e)
+        }
+    }
+}
+
 fn extract_zip(zip_path: &Path, dest: &Path, logger: &mut Logger) -> Result<(), String> {
     logger.log("Extracting archive");
     let file = fs::File::open(zip_path).map_err(|e| format!("Open zip: {e}"))?;
@@ -361,13 +790,25 @@ fn run() -> Result<(), String> {
     let args: Vec<String> = env::args().collect();
 
     let mut skip_update = false;
+    let mut staged_mode = false;
     let mut app_dir_arg: Option<PathBuf> = None;
-    for arg in args.iter().skip(1) {
+    let mut channel_arg: Option<String> = None;
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
         if arg == "--no-update" {
             skip_update = true;
+        } else if arg == "--staged" {
+            staged_mode = true;
+        } else if arg == "--channel" {
+            if let Some(value) = args.get(i + 1) {
+                channel_arg = Some(value.clone());
+                i += 1;
+            }
         } else if !arg.starts_with('-') && app_dir_arg.is_none() {
             app_dir_arg = Some(PathBuf::from(arg));
         }
+        i += 1;
     }
 
     let app_dir = app_dir_arg.unwrap_or_else(default_app_dir);
@@ -375,6 +816,17 @@ fn run() -> Result<(), String> {
     logger.log("Updater started");
     logger.log(format!("Using app dir: {}", pretty_path(&app_dir, &app_dir)));
 
+    let channel = channel_arg
+        .as_deref()
+        .and_then(Channel::parse)
+        .or_else(|| {
+            fs::read_to_string(app_dir.join("CHANNEL"))
+                .ok()
+                .and_then(|s| Channel::parse(&s))
+        })
+        .unwrap_or(Channel::Stable);
+    logger.log(format!("Using channel: {}", channel.as_str()));
+
     let primary_app_path = app_dir.join(APP_EXE);
     let app_path = if primary_app_path.exists() {
         logger.log(format!(
@@ -395,6 +847,26 @@ fn run() -> Result<(), String> {
         .unwrap_or(APP_EXE)
         .to_string();
 
+    if app_dir.join(PENDING_VERSION_FILE).exists() {
+        if is_app_running(&app_process_name, &mut logger) {
+            logger.log("Staged update pending but app is running; will apply on next clean shutdown");
+            return Ok(());
+        }
+
+        match apply_pending_update(&app_dir, &mut logger) {
+            Ok(true) => {
+                launch_app(&app_path, &app_dir, &mut logger, &[]);
+                return Ok(());
+            }
+            Ok(false) => {
+                logger.log("Continuing with update check after discarding stale pending update");
+            }
+            Err(e) => {
+                logger.log(format!("ERROR: Failed to apply staged update: {e}"));
+            }
+        }
+    }
+
     if skip_update {
         if is_app_running(&app_process_name, &mut logger) {
             logger.log("--no-update set and app already running; skipping update check");
@@ -412,7 +884,9 @@ fn run() -> Result<(), String> {
         .build()
         .map_err(|e| format!("HTTP error: {e}"))?;
 
-    let Some((tag, asset)) = check_for_update(&client, REPO, &current, &mut logger) else {
+    let Some((tag, asset, sig_asset)) =
+        check_for_update(&client, REPO, &current, channel, &mut logger)
+    else {
         logger.log("App is up to date");
         launch_app(&app_path, &app_dir, &mut logger, &[]);
         return Ok(());
@@ -427,16 +901,100 @@ fn run() -> Result<(), String> {
         return Err(e);
     }
 
+    let sig_str = match download_text(&client, &sig_asset.browser_download_url, &mut logger) {
+        Ok(s) => s,
+        Err(e) => {
+            logger.log(format!("ERROR: {e}"));
+            return Err(e);
+        }
+    };
+
+    let zip_bytes = fs::read(&temp_zip).map_err(|e| format!("Read downloaded zip: {e}"))?;
+    if let Err(e) = verify_signature(&zip_bytes, &sig_str, &mut logger) {
+        logger.log(format!("ERROR: {e}"));
+        return Err(e);
+    }
+
+    if staged_mode {
+        logger.log("Staged mode: extracting to staging area without touching the live install");
+        let staged_dir = app_dir.join(STAGED_DIR);
+        if staged_dir.exists() {
+            fs::remove_dir_all(&staged_dir).map_err(|e| format!("Clear stale staging dir: {e}"))?;
+        }
+        fs::create_dir_all(&staged_dir).map_err(|e| format!("Create staging dir: {e}"))?;
+
+        if let Err(e) = extract_zip(&temp_zip, &staged_dir, &mut logger) {
+            logger.log(format!("ERROR: {e}"));
+            return Err(e);
+        }
+
+        fs::write(app_dir.join(PENDING_VERSION_FILE), latest.to_string())
+            .map_err(|e| format!("Write pending marker: {e}"))?;
+        let _ = fs::remove_file(&temp_zip);
+        let _ = fs::remove_file(download_meta_path(&temp_zip));
+        logger.log(format!("Update staged: v{latest}"));
+
+        let poll_interval = Duration::from_secs(2);
+        let mut waited = Duration::ZERO;
+        while is_app_running(&app_process_name, &mut logger) && waited < APPLY_POLL_TIMEOUT {
+            thread::sleep(poll_interval);
+            waited += poll_interval;
+        }
+
+        if is_app_running(&app_process_name, &mut logger) {
+            logger.log("App still running; staged update will apply on next clean shutdown");
+            return Ok(());
+        }
+
+        if let Err(e) = apply_pending_update(&app_dir, &mut logger) {
+            logger.log(format!("ERROR: {e}"));
+            return Err(e);
+        }
+
+        logger.log(format!("Update complete: v{latest}"));
+        let launch_after_update = if primary_app_path.exists() {
+            primary_app_path
+        } else {
+            find_app_exe(&app_dir, &mut logger).unwrap_or(app_path)
+        };
+        launch_app(&launch_after_update, &app_dir, &mut logger, &[]);
+        return Ok(());
+    }
+
     kill_app(&app_process_name, &mut logger);
 
+    let backup_dir = match create_backup(&app_dir, &mut logger) {
+        Ok(dir) => dir,
+        Err(e) => {
+            logger.log(format!("ERROR: {e}"));
+            return Err(e);
+        }
+    };
+
     if let Err(e) = extract_zip(&temp_zip, &app_dir, &mut logger) {
         logger.log(format!("ERROR: {e}"));
+        restore_backup(&app_dir, &backup_dir, &mut logger);
+        logger.log(format!("Rolled back to v{current}"));
+        launch_app(&app_path, &app_dir, &mut logger, &[]);
+        return Err(e);
+    }
+
+    if let Err(e) = fs::write(app_dir.join("VERSION"), latest.to_string()) {
+        let e = format!("Write version: {e}");
+        logger.log(format!("ERROR: {e}"));
+        restore_backup(&app_dir, &backup_dir, &mut logger);
+        logger.log(format!("Rolled back to v{current}"));
+        launch_app(&app_path, &app_dir, &mut logger, &[]);
         return Err(e);
     }
 
-    fs::write(app_dir.join("VERSION"), latest.to_string())
-        .map_err(|e| format!("Write version: {e}"))?;
     let _ = fs::remove_file(&temp_zip);
+    let _ = fs::remove_file(download_meta_path(&temp_zip));
+    let _ = fs::remove_dir_all(&backup_dir);
+
+    // This update just superseded anything a previous --staged run left pending.
+    let _ = fs::remove_dir_all(app_dir.join(STAGED_DIR));
+    let _ = fs::remove_file(app_dir.join(PENDING_VERSION_FILE));
 
     schedule_updater_replacement(&app_dir, &mut logger);
 